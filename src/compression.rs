@@ -0,0 +1,112 @@
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use goose::goose::{Transaction, TransactionFunction};
+use goose::prelude::*;
+
+use crate::APP_USER_AGENT;
+
+/// Which response encodings the load-test client advertises via `Accept-Encoding`,
+/// selected per run via `--compression_modes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressionMode {
+    None,
+    Gzip,
+    Brotli,
+    Zstd,
+    All,
+}
+
+impl CompressionMode {
+    /// Parses a comma-separated `--compression_modes` value, e.g. "none,gzip,zstd".
+    pub fn parse_list(raw: &str) -> Result<Vec<CompressionMode>, Box<dyn Error>> {
+        raw.split(',').map(|mode| Self::parse(mode.trim())).collect()
+    }
+
+    fn parse(raw: &str) -> Result<CompressionMode, Box<dyn Error>> {
+        match raw.to_lowercase().as_str() {
+            "none" => Ok(CompressionMode::None),
+            "gzip" => Ok(CompressionMode::Gzip),
+            "brotli" => Ok(CompressionMode::Brotli),
+            "zstd" => Ok(CompressionMode::Zstd),
+            "all" => Ok(CompressionMode::All),
+            other => Err(format!("unknown compression mode: {}", other).into()),
+        }
+    }
+
+    /// A short, filename/scenario-safe tag for this mode, e.g. "gzip".
+    pub fn tag(&self) -> &'static str {
+        match self {
+            CompressionMode::None => "none",
+            CompressionMode::Gzip => "gzip",
+            CompressionMode::Brotli => "brotli",
+            CompressionMode::Zstd => "zstd",
+            CompressionMode::All => "all",
+        }
+    }
+
+    fn client_builder(&self) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder()
+            .user_agent(APP_USER_AGENT)
+            .cookie_store(true)
+            .timeout(Duration::from_secs(10));
+        match self {
+            CompressionMode::None => builder.no_gzip().no_brotli().no_zstd(),
+            CompressionMode::Gzip => builder.gzip(true).no_brotli().no_zstd(),
+            CompressionMode::Brotli => builder.brotli(true).no_gzip().no_zstd(),
+            CompressionMode::Zstd => builder.zstd(true).no_gzip().no_brotli(),
+            CompressionMode::All => builder.gzip(true).brotli(true).zstd(true),
+        }
+    }
+
+    /// Builds the on-start transaction that configures a `GooseUser`'s reqwest client
+    /// to advertise this compression mode.
+    pub fn configure_user_transaction(&self) -> Transaction {
+        let mode = *self;
+        let transaction_fn: TransactionFunction = Arc::new(move |user: &mut GooseUser| {
+            Box::pin(async move {
+                user.set_client_builder(mode.client_builder()).await?;
+                Ok(())
+            })
+        });
+        Transaction::new(transaction_fn).set_on_start()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_list_accepts_known_modes_case_insensitively() {
+        let modes = CompressionMode::parse_list("none,Gzip,ZSTD").unwrap();
+        assert_eq!(
+            modes,
+            vec![
+                CompressionMode::None,
+                CompressionMode::Gzip,
+                CompressionMode::Zstd,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_list_rejects_unknown_mode() {
+        let err = CompressionMode::parse_list("none,deflate").unwrap_err();
+        assert!(err.to_string().contains("deflate"));
+    }
+
+    #[test]
+    fn tag_round_trips_through_parse_list() {
+        for mode in [
+            CompressionMode::None,
+            CompressionMode::Gzip,
+            CompressionMode::Brotli,
+            CompressionMode::Zstd,
+            CompressionMode::All,
+        ] {
+            assert_eq!(CompressionMode::parse_list(mode.tag()).unwrap(), vec![mode]);
+        }
+    }
+}
@@ -1,68 +1,66 @@
-use cloud_storage::client::Client;
+mod auth;
+mod compression;
+mod openapi;
+mod storage;
+mod websocket;
+
+use auth::AuthConfig;
+use compression::CompressionMode;
+use goose::goose::{Transaction, TransactionFunction};
 use goose::{config, logger::GooseLogFormat, prelude::*};
+use storage::StorageBackend;
 use std::{
     error::Error,
     fs, io,
     path::{Path, PathBuf},
     result::Result,
+    sync::Arc,
     time::Duration,
 };
 
 const REQUEST_LOG_FORMAT: GooseLogFormat = GooseLogFormat::Csv;
 static APP_USER_AGENT: &str = "http-load-tester/0.0.1";
 
-async fn configure_user_without_compression(user: &mut GooseUser) -> TransactionResult {
-    let builder = reqwest::Client::builder()
-        .user_agent(APP_USER_AGENT)
-        .cookie_store(true)
-        .no_brotli()
-        .no_gzip()
-        .timeout(Duration::from_secs(10));
-    user.set_client_builder(builder).await?;
-    Ok(())
-}
-
-async fn configure_user_with_compression(user: &mut GooseUser) -> TransactionResult {
-    let builder = reqwest::Client::builder()
-        .user_agent(APP_USER_AGENT)
-        .cookie_store(true)
-        .brotli(true)
-        .gzip(true)
-        .timeout(Duration::from_secs(10));
-    user.set_client_builder(builder).await?;
-    Ok(())
-}
-
-async fn loadtest_strings(user: &mut GooseUser) -> TransactionResult {
-    let _goose_metrics = user.get_named("/strings/hello", "hello").await?;
-    let _goose_metrics = user
-        .get_named("/strings/hello?name=cool%20gal", "hello-param")
-        .await?;
-    let _goose_metrics = user.get_named("/strings/hello?name=aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", "hello-compressed").await?;
-    let _goose_metrics = user
-        .get_named("/strings/async-hello", "async-hello")
-        .await?;
-    let _goose_metrics = user.get_named("/strings/lines?n=10000", "lines").await?;
-
-    Ok(())
-}
-
-async fn loadtest_static(user: &mut GooseUser) -> TransactionResult {
-    let _goose_metrics = user.get_named("/static/basic.html", "basic-html").await?;
-    let _goose_metrics = user.get_named("/static/scout.webp", "scout-img").await?;
-
-    Ok(())
-}
-
-async fn loadtest_math(user: &mut GooseUser) -> TransactionResult {
-    let _goose_metrics = user
-        .get_named("/math/power-reciprocals-alt?n=1000", "power-sum-easy")
-        .await?;
-    let _goose_metrics = user
-        .get_named("/math/power-reciprocals-alt?n=10000000", "power-sum-hard")
-        .await?;
-
-    Ok(())
+const STRINGS_REQUESTS: &[(&str, &str)] = &[
+    ("/strings/hello", "hello"),
+    ("/strings/hello?name=cool%20gal", "hello-param"),
+    ("/strings/hello?name=aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", "hello-compressed"),
+    ("/strings/async-hello", "async-hello"),
+    ("/strings/lines?n=10000", "lines"),
+];
+
+const STATIC_REQUESTS: &[(&str, &str)] = &[
+    ("/static/basic.html", "basic-html"),
+    ("/static/scout.webp", "scout-img"),
+];
+
+const MATH_REQUESTS: &[(&str, &str)] = &[
+    ("/math/power-reciprocals-alt?n=1000", "power-sum-easy"),
+    ("/math/power-reciprocals-alt?n=10000000", "power-sum-hard"),
+];
+
+/// Builds a transaction that issues a fixed list of named GETs, routing each one
+/// through `auth` (if configured) so signed/bearer headers are attached consistently.
+fn build_loadtest_transaction(
+    name: &'static str,
+    requests: &'static [(&'static str, &'static str)],
+    auth: Arc<Option<AuthConfig>>,
+) -> Transaction {
+    let transaction_fn: TransactionFunction = Arc::new(move |user: &mut GooseUser| {
+        let auth = auth.clone();
+        Box::pin(async move {
+            for (path, request_name) in requests {
+                match auth.as_ref() {
+                    Some(auth) => auth.get_named(user, path, request_name).await?,
+                    None => {
+                        let _goose_metrics = user.get_named(path, request_name).await?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    });
+    Transaction::new(transaction_fn).set_name(name)
 }
 
 fn compute_logs_path(
@@ -96,28 +94,30 @@ fn report_log_path(
     maybe_log_dir: &Option<String>,
     maybe_report_name: &Option<String>,
     iteration: usize,
-    compressed: bool,
+    mode: CompressionMode,
+    format: &str,
 ) -> PathBuf {
-    let suffix = if compressed {
-        "compressed-report.html"
-    } else {
-        "report.html"
-    };
-    log_path(maybe_log_dir, maybe_report_name, iteration, suffix)
+    let suffix = format!("{}-report.{}", mode.tag(), format);
+    log_path(maybe_log_dir, maybe_report_name, iteration, &suffix)
+}
+
+/// Maps a `--report_formats` entry to the content type used when uploading it.
+fn report_content_type(format: &str) -> &'static str {
+    match format {
+        "md" => "text/markdown",
+        "json" => "application/json",
+        _ => "text/html",
+    }
 }
 
 fn request_log_path(
     maybe_log_dir: &Option<String>,
     maybe_report_name: &Option<String>,
     iteration: usize,
-    compressed: bool,
+    mode: CompressionMode,
 ) -> PathBuf {
-    let suffix = if compressed {
-        "compressed-requests.csv"
-    } else {
-        "requests.csv"
-    };
-    log_path(maybe_log_dir, maybe_report_name, iteration, suffix)
+    let suffix = format!("{}-requests.csv", mode.tag());
+    log_path(maybe_log_dir, maybe_report_name, iteration, &suffix)
 }
 
 fn log_path(
@@ -131,46 +131,109 @@ fn log_path(
     path_buf
 }
 
-async fn maybe_copy_to_gcs(
-    maybe_bucket_name: &Option<String>,
+/// Confirms Goose actually wrote one report file per requested `--report_formats`
+/// entry. `config.report_file` is set to a comma-separated list of per-format paths on
+/// the assumption that the pinned Goose version writes one file per entry (a real,
+/// documented Goose feature); if it instead treats the field as a single scalar path,
+/// none of the per-format files exist and the run would otherwise silently produce no
+/// report at all. Fail loudly in that all-missing case rather than masking it.
+fn verify_reports_written(
+    maybe_log_dir: &Option<String>,
+    maybe_report_name: &Option<String>,
+    iteration: usize,
+    mode: CompressionMode,
+    report_formats: &[String],
+) -> Result<(), Box<dyn Error>> {
+    if report_formats.len() <= 1 {
+        return Ok(());
+    }
+
+    let missing: Vec<&String> = report_formats
+        .iter()
+        .filter(|format| {
+            !report_log_path(maybe_log_dir, maybe_report_name, iteration, mode, format).exists()
+        })
+        .collect();
+
+    if missing.len() == report_formats.len() {
+        return Err(format!(
+            "none of the requested --report_formats ({}) were written for iteration {} ({}); \
+             the pinned goose version may not support a comma-separated report_file for \
+             multiple formats",
+            report_formats.join(","),
+            iteration,
+            mode.tag()
+        )
+        .into());
+    }
+
+    for format in missing {
+        println!(
+            "Warning: goose did not write a \"{}\" report for iteration {} ({})",
+            format,
+            iteration,
+            mode.tag()
+        );
+    }
+
+    Ok(())
+}
+
+async fn maybe_upload_reports(
+    maybe_storage: &Option<StorageBackend>,
     maybe_report_name: &Option<String>,
     maybe_log_dir: &Option<String>,
     iteration: usize,
-    compressed: bool,
+    mode: CompressionMode,
+    report_formats: &[String],
+    compress_logs: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let bucket_name = match maybe_bucket_name {
-        Some(b) => b,
+    let storage = match maybe_storage {
+        Some(s) => s,
         _ => return Ok(()),
     };
-    if bucket_name.is_empty() {
-        return Ok(());
+
+    for format in report_formats {
+        let report_path =
+            report_log_path(maybe_log_dir, maybe_report_name, iteration, mode, format);
+        let bytes = match fs::read(&report_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                println!(
+                    "Skipping upload of {}: Goose did not write a \"{}\" report ({})",
+                    report_path.display(),
+                    format,
+                    e
+                );
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        storage
+            .put(
+                report_path.file_name().unwrap().to_str().unwrap(),
+                bytes,
+                report_content_type(format),
+            )
+            .await?;
     }
-    let client = Client::new();
-
-    let report_path = report_log_path(maybe_log_dir, maybe_report_name, iteration, compressed);
-    let f = fs::read(&report_path)?;
-    client
-        .object()
-        .create(
-            bucket_name.as_str(),
-            f,
-            report_path.file_name().unwrap().to_str().unwrap(),
-            "text/html",
-        )
-        .await?;
 
-    let request_csv_path =
-        request_log_path(maybe_log_dir, maybe_report_name, iteration, compressed);
-    let f = fs::read(&request_csv_path)?;
-    client
-        .object()
-        .create(
-            bucket_name.as_str(),
-            f,
-            request_csv_path.file_name().unwrap().to_str().unwrap(),
-            "text/csv",
-        )
-        .await?;
+    let request_csv_path = request_log_path(maybe_log_dir, maybe_report_name, iteration, mode);
+    let bytes = fs::read(&request_csv_path)?;
+    let csv_name = request_csv_path.file_name().unwrap().to_str().unwrap();
+    if compress_logs {
+        let compressed_bytes = zstd::encode_all(bytes.as_slice(), 0)?;
+        storage
+            .put_with_encoding(
+                &format!("{}.zst", csv_name),
+                compressed_bytes,
+                "text/csv",
+                Some("zstd"),
+            )
+            .await?;
+    } else {
+        storage.put(csv_name, bytes, "text/csv").await?;
+    }
 
     Ok(())
 }
@@ -179,49 +242,82 @@ async fn run_attack(
     config: &config::GooseConfiguration,
     log_dir: &Option<String>,
     report_name: &Option<String>,
-    bucket: &Option<String>,
+    storage: &Option<StorageBackend>,
     num_iterations: usize,
-    compressed: bool,
+    mode: CompressionMode,
+    openapi_scenarios: &Option<Vec<Scenario>>,
+    report_formats: &[String],
+    compress_logs: bool,
+    auth: &Arc<Option<AuthConfig>>,
+    ws_scenario: &Option<Scenario>,
 ) -> Result<(), Box<dyn Error>> {
     maybe_prep_log_dir(log_dir, report_name)?;
 
     for i in 1..=num_iterations {
-        println!("Commencing iteration {}", i);
+        println!("Commencing iteration {} ({})", i, mode.tag());
         let mut config = config.clone();
-        config.report_file = report_log_path(log_dir, report_name, i, compressed)
-            .to_str()
-            .unwrap()
-            .to_string();
-        config.request_log = request_log_path(log_dir, report_name, i, compressed)
+        config.report_file = report_formats
+            .iter()
+            .map(|format| {
+                report_log_path(log_dir, report_name, i, mode, format)
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        config.request_log = request_log_path(log_dir, report_name, i, mode)
             .to_str()
             .unwrap()
             .to_string();
 
         let mut attack = GooseAttack::initialize_with_config(config)?;
-        if compressed {
-            attack = attack.register_scenario(
-                scenario!("WithCompression")
-                    .register_transaction(
-                        transaction!(configure_user_with_compression).set_on_start(),
-                    )
-                    .register_transaction(transaction!(loadtest_strings).set_name("strings"))
-                    .register_transaction(transaction!(loadtest_static).set_name("static"))
-                    .register_transaction(transaction!(loadtest_math).set_name("math")),
-            );
+        let on_start = mode.configure_user_transaction();
+        let scenario_name = format!("Compression-{}", mode.tag());
+
+        if let Some(scenarios) = openapi_scenarios {
+            for scenario in scenarios {
+                attack = attack
+                    .register_scenario(scenario.clone().register_transaction(on_start.clone()));
+            }
         } else {
             attack = attack.register_scenario(
-                scenario!("NoCompression")
-                    .register_transaction(
-                        transaction!(configure_user_without_compression).set_on_start(),
-                    )
-                    .register_transaction(transaction!(loadtest_strings).set_name("strings"))
-                    .register_transaction(transaction!(loadtest_static).set_name("static"))
-                    .register_transaction(transaction!(loadtest_math).set_name("math")),
+                scenario!(&scenario_name)
+                    .register_transaction(on_start)
+                    .register_transaction(build_loadtest_transaction(
+                        "strings",
+                        STRINGS_REQUESTS,
+                        auth.clone(),
+                    ))
+                    .register_transaction(build_loadtest_transaction(
+                        "static",
+                        STATIC_REQUESTS,
+                        auth.clone(),
+                    ))
+                    .register_transaction(build_loadtest_transaction(
+                        "math",
+                        MATH_REQUESTS,
+                        auth.clone(),
+                    )),
             );
         }
-        attack.execute().await?;
 
-        maybe_copy_to_gcs(bucket, report_name, log_dir, i, compressed).await?;
+        if let Some(ws_scenario) = ws_scenario {
+            attack = attack.register_scenario(ws_scenario.clone());
+        }
+        attack.execute().await?;
+        verify_reports_written(log_dir, report_name, i, mode, report_formats)?;
+
+        maybe_upload_reports(
+            storage,
+            report_name,
+            log_dir,
+            i,
+            mode,
+            report_formats,
+            compress_logs,
+        )
+        .await?;
         println!("Completed iteration {}", i);
 
         if i < num_iterations {
@@ -244,16 +340,43 @@ async fn main() -> Result<(), Box<dyn Error>> {
         required --start_time st: String
         /// The steady state run time, after all users are hatched (e.g. 10s, 20m).
         required --run_time rt: String
-        /// The GCS bucket to write an HTML report file to, if any.
+        /// The GCS bucket to upload reports and request logs to, if any. Shorthand for
+        /// --storage_url gs://<bucket>; prefer --storage_url for other backends.
         optional --bucket b: String
+        /// Where to upload reports and request logs: s3://bucket/prefix,
+        /// gs://bucket/prefix, az://container/prefix, or file:///path. Overrides --bucket.
+        optional --storage_url su: String
         /// An optional subdirectory for metrics within the log_dir and the bucket, e.g. {report_name}/report.html.
         optional --report_name rn: String
         /// The local directory to write metrics to. Uses /tmp/ if unset. A subdirectory may be added via --report_name.
         optional --log_dir ld: String
         /// Number of iterations to run. Defaults to 1.
         optional --iterations i: usize
-        /// Whether or not to enable compression.
-        optional --compress c: bool
+        /// Comma-separated compression modes to run, one attack pass per mode: any of
+        /// "none", "gzip", "brotli", "zstd", "all". Defaults to "none,all".
+        optional --compression_modes cm: String
+        /// Zstd-compress the uploaded CSV request logs and upload them as `.csv.zst`
+        /// with a zstd content-encoding.
+        optional --compress_logs cl: bool
+        /// An OpenAPI v3 document (local path or URL) to derive scenarios from instead
+        /// of the built-in demo transactions.
+        optional --openapi oa: String
+        /// Comma-separated report formats to emit per iteration, e.g. "html,md,json".
+        /// Defaults to "html".
+        optional --report_formats rf: String
+        /// How to authenticate requests: "bearer" or "hmac". Unset means unauthenticated.
+        optional --auth_scheme as: String
+        /// The key ID sent with each request when using the hmac auth scheme.
+        optional --auth_key_id ak: String
+        /// The bearer token, or hmac signing secret, for the selected auth scheme.
+        optional --auth_secret asec: String
+        /// A WebSocket endpoint to load test (e.g. /ws/echo). Adds a WebSocket scenario
+        /// alongside the other scenarios.
+        optional --ws_path wp: String
+        /// Number of echoed messages to exchange per user iteration. Defaults to 10.
+        optional --ws_messages wm: usize
+        /// Size in bytes of each WebSocket message. Defaults to 64.
+        optional --ws_message_size wms: usize
     };
 
     let num_iterations = match options.iterations {
@@ -261,6 +384,48 @@ async fn main() -> Result<(), Box<dyn Error>> {
         _ => 1,
     };
 
+    let compression_modes: Vec<CompressionMode> = match &options.compression_modes {
+        Some(modes) => CompressionMode::parse_list(modes)?,
+        None => vec![CompressionMode::None, CompressionMode::All],
+    };
+
+    let compress_logs = options.compress_logs.unwrap_or(false);
+
+    let report_formats: Vec<String> = match &options.report_formats {
+        Some(formats) => formats.split(',').map(|f| f.trim().to_string()).collect(),
+        None => vec!["html".to_string()],
+    };
+
+    let auth = Arc::new(AuthConfig::parse(
+        &options.auth_scheme,
+        &options.auth_key_id,
+        &options.auth_secret,
+    )?);
+
+    let openapi_scenarios = match &options.openapi {
+        Some(spec_path) => {
+            let spec = openapi::load_spec(spec_path).await?;
+            Some(openapi::build_scenarios(&spec, &auth)?)
+        }
+        None => None,
+    };
+
+    let ws_scenario = options.ws_path.as_ref().map(|ws_path| {
+        websocket::build_scenario(
+            ws_path.clone(),
+            options.ws_messages.unwrap_or(10),
+            options.ws_message_size.unwrap_or(64),
+        )
+    });
+
+    let storage = match (&options.storage_url, &options.bucket) {
+        (Some(storage_url), _) => Some(StorageBackend::parse(storage_url)?),
+        (None, Some(bucket_name)) if !bucket_name.is_empty() => {
+            Some(StorageBackend::from_gcs_bucket(bucket_name)?)
+        }
+        _ => None,
+    };
+
     let mut configuration = config::GooseConfiguration::default();
     configuration.host = options.host.clone();
     configuration.users = Some(options.users);
@@ -268,27 +433,26 @@ async fn main() -> Result<(), Box<dyn Error>> {
     configuration.run_time = options.run_time.clone();
     configuration.request_format = Some(REQUEST_LOG_FORMAT);
 
-    run_attack(
-        &configuration,
-        &options.log_dir,
-        &options.report_name,
-        &options.bucket,
-        num_iterations,
-        false,
-    )
-    .await?;
-
-    tokio::time::sleep(Duration::from_secs(10)).await;
-
-    run_attack(
-        &configuration,
-        &options.log_dir,
-        &options.report_name,
-        &options.bucket,
-        num_iterations,
-        true,
-    )
-    .await?;
+    for (idx, mode) in compression_modes.iter().enumerate() {
+        if idx > 0 {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        }
+
+        run_attack(
+            &configuration,
+            &options.log_dir,
+            &options.report_name,
+            &storage,
+            num_iterations,
+            *mode,
+            &openapi_scenarios,
+            &report_formats,
+            compress_logs,
+            &auth,
+            &ws_scenario,
+        )
+        .await?;
+    }
 
     Ok(())
 }
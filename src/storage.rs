@@ -0,0 +1,158 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path as ObjectPath;
+use object_store::{Attribute, AttributeValue, Attributes, ObjectStore, PutOptions, PutPayload};
+
+/// Where to ship report and request-log files once a run finishes. Backed by the
+/// `object_store` crate for the cloud variants, with a plain filesystem fallback for
+/// local runs.
+pub enum StorageBackend {
+    Remote {
+        store: Arc<dyn ObjectStore>,
+        prefix: String,
+    },
+    Local {
+        dir: PathBuf,
+    },
+}
+
+impl StorageBackend {
+    /// Parses a `--storage_url` value: `s3://bucket/prefix`, `gs://bucket/prefix`,
+    /// `az://container/prefix`, or `file:///path`. Credentials for the cloud backends
+    /// are picked up from the environment (e.g. `AWS_*`, `GOOGLE_*`, `AZURE_*`).
+    pub fn parse(storage_url: &str) -> Result<Self, Box<dyn Error>> {
+        let (scheme, rest) = storage_url
+            .split_once("://")
+            .ok_or("storage_url must include a scheme, e.g. s3://bucket/prefix")?;
+
+        if scheme == "file" {
+            return Ok(StorageBackend::Local {
+                dir: PathBuf::from(rest),
+            });
+        }
+
+        let (bucket, prefix) = match rest.split_once('/') {
+            Some((bucket, prefix)) => (bucket, prefix.to_string()),
+            None => (rest, String::new()),
+        };
+
+        let store: Arc<dyn ObjectStore> = match scheme {
+            "s3" => Arc::new(
+                AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()?,
+            ),
+            "gs" => Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()?,
+            ),
+            "az" => Arc::new(
+                MicrosoftAzureBuilder::from_env()
+                    .with_container_name(bucket)
+                    .build()?,
+            ),
+            other => return Err(format!("unsupported storage scheme: {}", other).into()),
+        };
+
+        Ok(StorageBackend::Remote { store, prefix })
+    }
+
+    /// Builds the `gs://` backend used by the legacy `--bucket` flag.
+    pub fn from_gcs_bucket(bucket_name: &str) -> Result<Self, Box<dyn Error>> {
+        Self::parse(&format!("gs://{}", bucket_name))
+    }
+
+    /// Writes `bytes` under `name`, tagging the object with `content_type` where the
+    /// backend supports it.
+    pub async fn put(
+        &self,
+        name: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.put_with_encoding(name, bytes, content_type, None).await
+    }
+
+    /// Like [`StorageBackend::put`], but also tags the object with a
+    /// `Content-Encoding` (e.g. `"zstd"`) where the backend supports it.
+    pub async fn put_with_encoding(
+        &self,
+        name: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+        content_encoding: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        match self {
+            StorageBackend::Remote { store, prefix } => {
+                let full_path = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                let mut attributes = Attributes::new();
+                attributes.insert(
+                    Attribute::ContentType,
+                    AttributeValue::from(content_type.to_string()),
+                );
+                if let Some(encoding) = content_encoding {
+                    attributes.insert(
+                        Attribute::ContentEncoding,
+                        AttributeValue::from(encoding.to_string()),
+                    );
+                }
+                store
+                    .put_opts(
+                        &ObjectPath::from(full_path),
+                        PutPayload::from(bytes),
+                        PutOptions {
+                            attributes,
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                Ok(())
+            }
+            StorageBackend::Local { dir } => {
+                fs::create_dir_all(dir)?;
+                fs::write(dir.join(name), bytes)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_file_url_is_local() {
+        let backend = StorageBackend::parse("file:///tmp/reports").unwrap();
+        assert!(matches!(backend, StorageBackend::Local { dir } if dir == PathBuf::from("/tmp/reports")));
+    }
+
+    #[test]
+    fn parse_unsupported_scheme_is_an_error() {
+        let err = StorageBackend::parse("ftp://bucket/prefix").unwrap_err();
+        assert!(err.to_string().contains("unsupported storage scheme"));
+    }
+
+    #[test]
+    fn parse_without_scheme_is_an_error() {
+        let err = StorageBackend::parse("bucket/prefix").unwrap_err();
+        assert!(err.to_string().contains("must include a scheme"));
+    }
+
+    #[test]
+    fn from_gcs_bucket_matches_parse_gs_url() {
+        let backend = StorageBackend::from_gcs_bucket("my-bucket").unwrap();
+        assert!(matches!(backend, StorageBackend::Remote { prefix, .. } if prefix.is_empty()));
+    }
+}
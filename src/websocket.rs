@@ -0,0 +1,92 @@
+use std::error::Error;
+use std::io;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use goose::goose::{GooseUserData, Transaction, TransactionFunction};
+use goose::prelude::*;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// The open connection a user keeps alive between the on-start connect transaction and
+/// the repeating message-round-trip transactions.
+struct WebSocketSession {
+    stream: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+}
+
+impl GooseUserData for WebSocketSession {}
+
+/// Boxes a network error so it can be returned from a `TransactionFunction`, which
+/// marks the enclosing transaction as failed rather than aborting the user task.
+fn boxed(err: impl Error + Send + Sync + 'static) -> Box<dyn Error + Send + Sync> {
+    Box::new(err)
+}
+
+/// Builds the WebSocket scenario enabled by `--ws_path`: connects once per user
+/// (recorded as the `ws-connect` transaction, so its duration shows up as connect
+/// latency in the report), then performs `message_count` echoed round trips of
+/// `message_size` bytes, each registered as its own `ws-message` transaction so its
+/// round-trip time and pass/fail status land in the report as a named metric.
+/// Connect and send/receive failures (a refused or closed socket) are ordinary
+/// runtime conditions, so they fail the transaction rather than panicking the user.
+pub fn build_scenario(ws_path: String, message_count: usize, message_size: usize) -> Scenario {
+    let connect_fn: TransactionFunction = Arc::new(move |user: &mut GooseUser| {
+        let ws_path = ws_path.clone();
+        Box::pin(async move {
+            let mut url = user.base_url.clone();
+            url.set_scheme(if url.scheme() == "https" { "wss" } else { "ws" })
+                .expect("http(s) and ws(s) are both valid URL schemes");
+            url.set_path(&ws_path);
+
+            let (stream, _) = connect_async(url.as_str()).await.map_err(boxed)?;
+            user.set_session_data(WebSocketSession { stream });
+
+            Ok(())
+        })
+    });
+
+    let message_fn: TransactionFunction = Arc::new(move |user: &mut GooseUser| {
+        Box::pin(async move {
+            let Some(session) = user.get_session_data_mut::<WebSocketSession>() else {
+                // ws-connect failed to stash a session, but Goose still runs the
+                // non-on-start transactions; fail rather than recording a pass against
+                // a connection that was never established.
+                return Err(boxed(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "no websocket session: ws-connect did not succeed",
+                )));
+            };
+
+            let payload = "x".repeat(message_size);
+            session
+                .stream
+                .send(Message::Text(payload))
+                .await
+                .map_err(boxed)?;
+            session
+                .stream
+                .next()
+                .await
+                .ok_or_else(|| {
+                    boxed(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "websocket closed before echo was received",
+                    ))
+                })?
+                .map_err(boxed)?;
+
+            Ok(())
+        })
+    });
+
+    let mut scenario = scenario!("WebSocket").register_transaction(
+        Transaction::new(connect_fn)
+            .set_name("ws-connect")
+            .set_on_start(),
+    );
+    for _ in 0..message_count {
+        scenario = scenario
+            .register_transaction(Transaction::new(message_fn.clone()).set_name("ws-message"));
+    }
+    scenario
+}